@@ -2,13 +2,19 @@ use dlopen2::wrapper::Container;
 use dlopen2::wrapper::WrapperApi;
 use flagset::FlagSet;
 use semver::{Version, VersionReq};
+use std::cell::RefCell;
 use std::ffi::c_char;
 use std::ffi::c_void;
 use std::ffi::CStr;
 use std::ffi::CString;
 use std::ffi::OsStr;
 use std::fmt::Debug;
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::vec;
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(windows)]
+use std::os::windows::io::{AsRawSocket, RawSocket};
 
 #[repr(i32)]
 #[doc = " Result codes for operations, negative are errors, zero or positives are\n success."]
@@ -19,6 +25,7 @@ pub enum MndResult {
 	ErrorInvalidValue = -2,
 	ErrorConnectingFailed = -3,
 	ErrorOperationFailed = -4,
+	ErrorUnsupported = -5,
 }
 impl MndResult {
 	pub fn to_result(self) -> Result<(), MndResult> {
@@ -31,6 +38,7 @@ impl MndResult {
 }
 
 flagset::flags! {
+	#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 	#[doc = " Bitflags for client application state."]
 	pub enum ClientState: u32 {
 		ClientPrimaryApp = 1,
@@ -67,8 +75,6 @@ pub struct MonadoApi {
 		unsafe extern "C" fn(root: MndRootPtr, client_id: u32) -> MndResult,
 	mnd_root_set_client_focused:
 		unsafe extern "C" fn(root: MndRootPtr, client_id: u32) -> MndResult,
-	mnd_root_toggle_client_io_active:
-		unsafe extern "C" fn(root: MndRootPtr, client_id: u32) -> MndResult,
 	mnd_root_get_device_count:
 		unsafe extern "C" fn(root: MndRootPtr, out_device_count: *mut u32) -> MndResult,
 	mnd_root_get_device_info: unsafe extern "C" fn(
@@ -77,15 +83,74 @@ pub struct MonadoApi {
 		out_device_id: *mut u32,
 		out_dev_name: *mut *const ::std::os::raw::c_char,
 	) -> MndResult,
-	mnd_root_get_device_from_role: unsafe extern "C" fn(
-		root: MndRootPtr,
-		role_name: *const ::std::os::raw::c_char,
-		out_device_id: *mut i32,
-	) -> MndResult,
+	// `crate_api_version` now accepts a range of 1.x runtimes instead of
+	// pinning exactly 1.0.0, so these are marked optional for defensive
+	// tolerance of a runtime that resolves a matching version but is
+	// missing a symbol, rather than refusing to load outright.
+	// Availability is exposed through `Monado::supports`.
+	mnd_root_toggle_client_io_active:
+		Option<unsafe extern "C" fn(root: MndRootPtr, client_id: u32) -> MndResult>,
+	mnd_root_get_device_from_role: Option<
+		unsafe extern "C" fn(
+			root: MndRootPtr,
+			role_name: *const ::std::os::raw::c_char,
+			out_device_id: *mut i32,
+		) -> MndResult,
+	>,
+	mnd_root_get_ipc_fd: Option<unsafe extern "C" fn(root: MndRootPtr, out_fd: *mut i32) -> MndResult>,
+}
+
+#[doc = " Optional capabilities that depend on the version of the running\n `libmonado.so`. Use [`Monado::supports`] to check before relying on the\n methods gated by a given variant."]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum MonadoFeature {
+	#[doc = " [`Client::set_io_active`]"]
+	IoActiveToggle,
+	#[doc = " [`Monado::device_from_role`]"]
+	DeviceFromRole,
+	#[doc = " [`Monado`]'s `AsRawFd`/`AsRawSocket` impl exposes a real IPC socket\n handle rather than a dummy one."]
+	PollableFd,
+}
+
+#[doc = " Owned, serializable copy of a client's name and state at the moment\n [`Monado::snapshot`] was taken. Unlike [`Client`], it does not borrow\n the `Monado` it was read from."]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientSnapshot {
+	pub id: u32,
+	pub name: String,
+	#[doc = " `ClientState` flags, flattened to their variant names (e.g.\n `\"ClientSessionFocused\"`), for consumers that don't link `flagset`."]
+	pub state: Vec<String>,
+}
+
+#[doc = " Owned, serializable copy of a device's id and name at the moment\n [`Monado::snapshot`] was taken."]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceSnapshot {
+	pub id: u32,
+	pub name: String,
+}
+
+#[doc = " Eagerly collected view of every client and device known to a\n [`Monado`], produced by [`Monado::snapshot`]. Serializable behind the\n `serde` feature, e.g. for a `monado-cli --format json` style dumper."]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeSnapshot {
+	pub clients: Vec<ClientSnapshot>,
+	pub devices: Vec<DeviceSnapshot>,
+}
+
+#[doc = " A single client or device change observed between two calls to\n [`Monado::poll_events`]."]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum MonadoEvent {
+	ClientAdded { client_id: u32 },
+	ClientRemoved { client_id: u32 },
+	ClientStateChanged { client_id: u32 },
+	ClientPrimaryChanged { client_id: u32 },
+	ClientFocusChanged { client_id: u32 },
+	DeviceConnected { device_id: u32 },
+	DeviceDisconnected { device_id: u32 },
 }
 
 fn crate_api_version() -> VersionReq {
-	VersionReq::parse("=1.0.0").unwrap()
+	VersionReq::parse(">=1.0.0, <2.0.0").unwrap()
 }
 fn get_api_version(api: &Container<MonadoApi>) -> Version {
 	let mut major = 0;
@@ -99,23 +164,77 @@ fn get_api_version(api: &Container<MonadoApi>) -> Version {
 pub struct Monado {
 	api: Container<MonadoApi>,
 	root: MndRootPtr,
+	version: Version,
+	ipc_fd: Option<i32>,
+	prev_clients: RefCell<Option<Vec<(u32, u32)>>>,
+	prev_devices: RefCell<Option<Vec<u32>>>,
 }
 impl Monado {
 	pub fn create<S: AsRef<OsStr>>(libmonado_so: S) -> Result<Self, MndResult> {
 		let api = unsafe { Container::<MonadoApi>::load(libmonado_so) }
 			.map_err(|_| MndResult::ErrorConnectingFailed)?;
-		if !crate_api_version().matches(&get_api_version(&api)) {
+		let version = get_api_version(&api);
+		if !crate_api_version().matches(&version) {
 			return Err(MndResult::ErrorInvalidVersion);
 		}
 		let mut root = std::ptr::null_mut();
 		unsafe {
 			api.mnd_root_create(&mut root).to_result()?;
 		}
-		Ok(Monado { api, root })
+		let ipc_fd = api.mnd_root_get_ipc_fd.and_then(|get_ipc_fd| {
+			let mut fd = -1;
+			let result = unsafe { get_ipc_fd(root, &mut fd) };
+			(result == MndResult::Success).then_some(fd)
+		});
+		Ok(Monado {
+			api,
+			root,
+			version,
+			ipc_fd,
+			prev_clients: RefCell::new(None),
+			prev_devices: RefCell::new(None),
+		})
 	}
 
 	pub fn get_api_version(&self) -> Version {
-		get_api_version(&self.api)
+		self.version.clone()
+	}
+
+	#[doc = " Whether the connected runtime resolved the symbols backing `feature`.\n Methods gated on an unsupported feature return\n `Err(MndResult::ErrorUnsupported)` instead of panicking or silently\n no-op-ing."]
+	pub fn supports(&self, feature: MonadoFeature) -> bool {
+		match feature {
+			MonadoFeature::IoActiveToggle => self.api.mnd_root_toggle_client_io_active.is_some(),
+			MonadoFeature::DeviceFromRole => self.api.mnd_root_get_device_from_role.is_some(),
+			MonadoFeature::PollableFd => self.ipc_fd.is_some(),
+		}
+	}
+
+	#[doc = " Drain client and device changes since the last call.\n\n The first call after `create` establishes the baseline and always\n returns an empty list. Intended to be called in response to the IPC\n socket (see [`AsRawFd`]/`AsRawSocket`) becoming readable, instead of\n polling `clients()`/`devices()` in a loop."]
+	pub fn poll_events(&self) -> Result<Vec<MonadoEvent>, MndResult> {
+		let mut current_clients = Vec::new();
+		for mut client in self.clients()? {
+			let state = client.state()?;
+			current_clients.push((client.id, state.bits()));
+		}
+		let mut current_devices = Vec::new();
+		for device in self.devices()? {
+			current_devices.push(device.id);
+		}
+
+		let mut events = Vec::new();
+		let mut prev_clients = self.prev_clients.borrow_mut();
+		if let Some(prev) = prev_clients.as_ref() {
+			diff_clients(prev, &current_clients, &mut events);
+		}
+		*prev_clients = Some(current_clients);
+
+		let mut prev_devices = self.prev_devices.borrow_mut();
+		if let Some(prev) = prev_devices.as_ref() {
+			diff_devices(prev, &current_devices, &mut events);
+		}
+		*prev_devices = Some(current_devices);
+
+		Ok(events)
 	}
 
 	pub fn clients<'m>(&'m self) -> Result<impl IntoIterator<Item = Client<'m>>, MndResult> {
@@ -149,13 +268,15 @@ impl Monado {
 	// @param role_name Name of the role
 	// @param out_device_id Pointer to populate with device id
 	pub fn device_from_role<'m>(&'m self, role_name: &str) -> Result<Device<'m>, MndResult> {
+		let get_device_from_role = self
+			.api
+			.mnd_root_get_device_from_role
+			.ok_or(MndResult::ErrorUnsupported)?;
 		let c_name = CString::new(role_name).unwrap();
 		let mut device_id = -1;
 
 		unsafe {
-			self.api
-				.mnd_root_get_device_from_role(self.root, c_name.as_ptr(), &mut device_id)
-				.to_result()?
+			get_device_from_role(self.root, c_name.as_ptr(), &mut device_id).to_result()?
 		};
 		let mut id = 0;
 		let mut c_name: *const c_char = std::ptr::null_mut();
@@ -208,6 +329,31 @@ impl Monado {
 		}
 		Ok(devices.into_iter().flatten())
 	}
+
+	#[doc = " Eagerly collect every client (name + state) and device (id + name)\n into an owned [`RuntimeSnapshot`] that outlives `self`, instead of the\n borrowed [`Client`]/[`Device`] iterators returned by `clients()`/\n `devices()`."]
+	pub fn snapshot(&self) -> Result<RuntimeSnapshot, MndResult> {
+		let mut clients = Vec::new();
+		for mut client in self.clients()? {
+			let id = client.id;
+			let name = client.name()?;
+			let state = client
+				.state()?
+				.into_iter()
+				.map(|flag| format!("{flag:?}"))
+				.collect();
+			clients.push(ClientSnapshot { id, name, state });
+		}
+
+		let mut devices = Vec::new();
+		for device in self.devices()? {
+			devices.push(DeviceSnapshot {
+				id: device.id,
+				name: device.name,
+			});
+		}
+
+		Ok(RuntimeSnapshot { clients, devices })
+	}
 }
 impl Drop for Monado {
 	fn drop(&mut self) {
@@ -215,6 +361,71 @@ impl Drop for Monado {
 	}
 }
 
+// SAFETY: `root` is an opaque handle into libmonado that is only ever
+// dereferenced by the C library itself, never by us, so moving it to
+// another thread is sound. `Monado` still isn't `Sync` (its `RefCell`
+// caches aren't), which is what forces the one-thread-at-a-time access
+// that `mnd_root_*` calls require; [`SharedMonado`] adds a mutex on top
+// so the handle can additionally be *shared* across threads.
+unsafe impl Send for Monado {}
+
+#[cfg(unix)]
+impl AsRawFd for Monado {
+	fn as_raw_fd(&self) -> RawFd {
+		self.ipc_fd.unwrap_or(-1)
+	}
+}
+#[cfg(windows)]
+impl AsRawSocket for Monado {
+	fn as_raw_socket(&self) -> RawSocket {
+		self.ipc_fd.map_or(RawSocket::MAX, |fd| fd as RawSocket)
+	}
+}
+
+fn diff_clients(prev: &[(u32, u32)], current: &[(u32, u32)], events: &mut Vec<MonadoEvent>) {
+	// `ClientState as u32` would give the enum's positional discriminant,
+	// not its bitmask value, so go through `FlagSet::from` (which the
+	// `flags!` macro wires up to the real bit) to get the mask instead.
+	let primary_bit = FlagSet::from(ClientState::ClientPrimaryApp).bits();
+	let focus_bit = FlagSet::from(ClientState::ClientSessionFocused).bits();
+	for &(client_id, state) in current {
+		match prev.iter().find(|&&(id, _)| id == client_id) {
+			None => events.push(MonadoEvent::ClientAdded { client_id }),
+			Some(&(_, prev_state)) if prev_state != state => {
+				let changed = prev_state ^ state;
+				if changed & primary_bit != 0 {
+					events.push(MonadoEvent::ClientPrimaryChanged { client_id });
+				}
+				if changed & focus_bit != 0 {
+					events.push(MonadoEvent::ClientFocusChanged { client_id });
+				}
+				if changed & !(primary_bit | focus_bit) != 0 {
+					events.push(MonadoEvent::ClientStateChanged { client_id });
+				}
+			}
+			Some(_) => {}
+		}
+	}
+	for &(client_id, _) in prev {
+		if !current.iter().any(|&(id, _)| id == client_id) {
+			events.push(MonadoEvent::ClientRemoved { client_id });
+		}
+	}
+}
+
+fn diff_devices(prev: &[u32], current: &[u32], events: &mut Vec<MonadoEvent>) {
+	for &device_id in current {
+		if !prev.contains(&device_id) {
+			events.push(MonadoEvent::DeviceConnected { device_id });
+		}
+	}
+	for &device_id in prev {
+		if !current.contains(&device_id) {
+			events.push(MonadoEvent::DeviceDisconnected { device_id });
+		}
+	}
+}
+
 #[derive(Clone)]
 pub struct Client<'m> {
 	monado: &'m Monado,
@@ -262,19 +473,134 @@ impl Client<'_> {
 		}
 	}
 	pub fn set_io_active(&mut self, active: bool) -> Result<(), MndResult> {
+		let toggle_io_active = self
+			.monado
+			.api
+			.mnd_root_toggle_client_io_active
+			.ok_or(MndResult::ErrorUnsupported)?;
 		let state = self.state()?;
 		if state.contains(ClientState::ClientIoActive) != active {
 			unsafe {
-				self.monado
-					.api
-					.mnd_root_toggle_client_io_active(self.monado.root, self.id)
-					.to_result()?;
+				toggle_io_active(self.monado.root, self.id).to_result()?;
 			}
 		}
 		Ok(())
 	}
 }
 
+#[doc = " `Send`/`Sync` handle around a [`Monado`], guarded by an internal mutex\n so every `mnd_root_*` call for the wrapped root is serialized. Clone it\n freely and move clones into a thread pool or a worker thread; enumerate\n with [`SharedMonado::clients`]/`devices` and drive per-client actions\n through the returned [`SharedClient`] handles."]
+#[derive(Clone)]
+pub struct SharedMonado(Arc<Mutex<Monado>>);
+impl SharedMonado {
+	pub fn new(monado: Monado) -> Self {
+		SharedMonado(Arc::new(Mutex::new(monado)))
+	}
+
+	fn lock(&self) -> MutexGuard<'_, Monado> {
+		self.0.lock().expect("Monado mutex poisoned")
+	}
+
+	pub fn get_api_version(&self) -> Version {
+		self.lock().get_api_version()
+	}
+
+	pub fn supports(&self, feature: MonadoFeature) -> bool {
+		self.lock().supports(feature)
+	}
+
+	pub fn poll_events(&self) -> Result<Vec<MonadoEvent>, MndResult> {
+		self.lock().poll_events()
+	}
+
+	pub fn snapshot(&self) -> Result<RuntimeSnapshot, MndResult> {
+		self.lock().snapshot()
+	}
+
+	pub fn clients(&self) -> Result<Vec<SharedClient>, MndResult> {
+		let monado = self.lock();
+		let clients: Vec<SharedClient> = monado
+			.clients()?
+			.into_iter()
+			.map(|client| SharedClient {
+				monado: Arc::clone(&self.0),
+				id: client.id,
+			})
+			.collect();
+		Ok(clients)
+	}
+
+	pub fn devices(&self) -> Result<Vec<DeviceSnapshot>, MndResult> {
+		let monado = self.lock();
+		let devices: Vec<DeviceSnapshot> = monado
+			.devices()?
+			.into_iter()
+			.map(|device| DeviceSnapshot {
+				id: device.id,
+				name: device.name,
+			})
+			.collect();
+		Ok(devices)
+	}
+
+	pub fn device_from_role(&self, role_name: &str) -> Result<DeviceSnapshot, MndResult> {
+		let monado = self.lock();
+		let device = monado.device_from_role(role_name)?;
+		Ok(DeviceSnapshot {
+			id: device.id,
+			name: device.name,
+		})
+	}
+}
+
+#[doc = " Cloneable, thread-movable counterpart to [`Client`]. Each call locks\n the shared [`Monado`] only for the duration of the FFI call, so a\n handle can be stashed in a worker thread and driven from there."]
+#[derive(Clone)]
+pub struct SharedClient {
+	monado: Arc<Mutex<Monado>>,
+	id: u32,
+}
+impl SharedClient {
+	pub fn name(&self) -> Result<String, MndResult> {
+		let monado = self.monado.lock().expect("Monado mutex poisoned");
+		Client {
+			monado: &monado,
+			id: self.id,
+		}
+		.name()
+	}
+	pub fn state(&self) -> Result<FlagSet<ClientState>, MndResult> {
+		let monado = self.monado.lock().expect("Monado mutex poisoned");
+		Client {
+			monado: &monado,
+			id: self.id,
+		}
+		.state()
+	}
+	pub fn set_primary(&self) -> Result<(), MndResult> {
+		let monado = self.monado.lock().expect("Monado mutex poisoned");
+		Client {
+			monado: &monado,
+			id: self.id,
+		}
+		.set_primary()
+	}
+	pub fn set_focused(&self) -> Result<(), MndResult> {
+		let monado = self.monado.lock().expect("Monado mutex poisoned");
+		Client {
+			monado: &monado,
+			id: self.id,
+		}
+		.set_focused()
+	}
+	pub fn set_io_active(&self, active: bool) -> Result<(), MndResult> {
+		let monado = self.monado.lock().expect("Monado mutex poisoned");
+		Client {
+			monado: &monado,
+			id: self.id,
+		}
+		.set_io_active(active)
+	}
+}
+
 #[derive(Clone)]
 pub struct Device<'m> {
 	_monado: &'m Monado,
@@ -301,4 +627,356 @@ impl Debug for Device<'_> {
 // 			client.state().unwrap()
 // 		)
 // 	}
-// }
\ No newline at end of file
+// }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn state(flags: &[ClientState]) -> u32 {
+		flags
+			.iter()
+			.fold(0u32, |acc, &f| acc | FlagSet::from(f).bits())
+	}
+
+	#[test]
+	fn diff_clients_reports_added_and_removed() {
+		let prev = vec![(1, 0)];
+		let current = vec![(1, 0), (2, 0)];
+		let mut events = Vec::new();
+		diff_clients(&prev, &current, &mut events);
+		assert_eq!(events, vec![MonadoEvent::ClientAdded { client_id: 2 }]);
+
+		let prev = vec![(1, 0), (2, 0)];
+		let current = vec![(1, 0)];
+		let mut events = Vec::new();
+		diff_clients(&prev, &current, &mut events);
+		assert_eq!(events, vec![MonadoEvent::ClientRemoved { client_id: 2 }]);
+	}
+
+	#[test]
+	fn diff_clients_reports_primary_and_focus_changes() {
+		let prev = vec![(1, 0)];
+		let current = vec![(1, state(&[ClientState::ClientPrimaryApp]))];
+		let mut events = Vec::new();
+		diff_clients(&prev, &current, &mut events);
+		assert_eq!(events, vec![MonadoEvent::ClientPrimaryChanged { client_id: 1 }]);
+
+		let prev = vec![(1, 0)];
+		let current = vec![(1, state(&[ClientState::ClientSessionFocused]))];
+		let mut events = Vec::new();
+		diff_clients(&prev, &current, &mut events);
+		assert_eq!(events, vec![MonadoEvent::ClientFocusChanged { client_id: 1 }]);
+	}
+
+	#[test]
+	fn diff_clients_reports_other_state_changes_separately_from_primary_and_focus() {
+		let prev = vec![(1, 0)];
+		let current = vec![(1, state(&[ClientState::ClientSessionActive]))];
+		let mut events = Vec::new();
+		diff_clients(&prev, &current, &mut events);
+		assert_eq!(events, vec![MonadoEvent::ClientStateChanged { client_id: 1 }]);
+
+		// A bit flip that touches primary, focus, and another flag at once
+		// should report all three events.
+		let prev = vec![(1, 0)];
+		let current = vec![(
+			1,
+			state(&[
+				ClientState::ClientPrimaryApp,
+				ClientState::ClientSessionFocused,
+				ClientState::ClientIoActive,
+			]),
+		)];
+		let mut events = Vec::new();
+		diff_clients(&prev, &current, &mut events);
+		assert_eq!(
+			events,
+			vec![
+				MonadoEvent::ClientPrimaryChanged { client_id: 1 },
+				MonadoEvent::ClientFocusChanged { client_id: 1 },
+				MonadoEvent::ClientStateChanged { client_id: 1 },
+			]
+		);
+	}
+
+	#[test]
+	fn diff_clients_is_quiet_when_nothing_changed() {
+		let prev = vec![(1, state(&[ClientState::ClientSessionActive]))];
+		let current = prev.clone();
+		let mut events = Vec::new();
+		diff_clients(&prev, &current, &mut events);
+		assert!(events.is_empty());
+	}
+
+	#[test]
+	fn diff_devices_reports_connected_and_disconnected() {
+		let prev = vec![1, 2];
+		let current = vec![2, 3];
+		let mut events = Vec::new();
+		diff_devices(&prev, &current, &mut events);
+		assert_eq!(
+			events,
+			vec![
+				MonadoEvent::DeviceConnected { device_id: 3 },
+				MonadoEvent::DeviceDisconnected { device_id: 1 },
+			]
+		);
+	}
+}
+// `SharedMonado`/`SharedClient` can only be exercised against a real
+// `Container<MonadoApi>`, which in turn needs a real shared library to
+// `dlopen`. These tests compile a tiny fake `libmonado.so` on the fly and
+// drive it from multiple threads through `SharedMonado`. The stub does
+// NOT synchronize itself internally (no mutex of its own) — instead each
+// exported function other than `mnd_api_get_version`, `mnd_root_create`
+// and `mnd_root_destroy` flips a non-blocking `AtomicBool` reentrancy guard
+// and panics if it's already set, so a regression that removed
+// `SharedMonado`'s locking would actually be caught here, rather than
+// being silently masked by the stub protecting itself.
+#[cfg(all(test, unix))]
+mod shared_monado_tests {
+	use super::*;
+	use std::thread;
+
+	const STUB_SOURCE: &str = r#"
+use std::cell::UnsafeCell;
+use std::ffi::{c_char, c_void, CString};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+struct ClientRec {
+	id: u32,
+	name: CString,
+	state: u32,
+}
+
+// Deliberately unsynchronized: `busy` is a reentrancy detector, not a
+// lock. If two calls for this root ever overlap, the second `swap` sees
+// `true` and panics instead of silently serializing the access.
+struct Root {
+	busy: AtomicBool,
+	clients: UnsafeCell<Vec<ClientRec>>,
+}
+
+unsafe fn root_ref<'a>(root: *mut c_void) -> &'a Root {
+	unsafe { &*(root as *mut Root) }
+}
+
+fn guarded(root: *mut c_void, body: impl FnOnce(&mut Vec<ClientRec>) -> i32) -> i32 {
+	let root = unsafe { root_ref(root) };
+	if root.busy.swap(true, Ordering::SeqCst) {
+		panic!("reentrant mnd_root_* call detected: SharedMonado failed to serialize calls");
+	}
+	// Hold the "critical section" open briefly to widen the window a
+	// broken caller would need to race into.
+	std::thread::sleep(Duration::from_micros(200));
+	let clients = unsafe { &mut *root.clients.get() };
+	let result = body(clients);
+	root.busy.store(false, Ordering::SeqCst);
+	result
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mnd_api_get_version(major: *mut u32, minor: *mut u32, patch: *mut u32) {
+	unsafe {
+		*major = 1;
+		*minor = 0;
+		*patch = 0;
+	}
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mnd_root_create(out_root: *mut *mut c_void) -> i32 {
+	let root = Box::new(Root {
+		busy: AtomicBool::new(false),
+		clients: UnsafeCell::new(vec![
+			ClientRec { id: 1, name: CString::new("alpha").unwrap(), state: 0 },
+			ClientRec { id: 2, name: CString::new("beta").unwrap(), state: 0 },
+		]),
+	});
+	unsafe { *out_root = Box::into_raw(root) as *mut c_void };
+	0
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mnd_root_destroy(out_root: *mut *mut c_void) {
+	unsafe {
+		let ptr = *out_root as *mut Root;
+		if !ptr.is_null() {
+			drop(Box::from_raw(ptr));
+		}
+		*out_root = std::ptr::null_mut();
+	}
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mnd_root_update_client_list(root: *mut c_void) -> i32 {
+	guarded(root, |_clients| 0)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mnd_root_get_number_clients(root: *mut c_void, out_num: *mut u32) -> i32 {
+	guarded(root, |clients| {
+		unsafe { *out_num = clients.len() as u32 };
+		0
+	})
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mnd_root_get_client_id_at_index(
+	root: *mut c_void,
+	index: u32,
+	out_id: *mut u32,
+) -> i32 {
+	guarded(root, |clients| {
+		unsafe { *out_id = clients[index as usize].id };
+		0
+	})
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mnd_root_get_client_name(
+	root: *mut c_void,
+	client_id: u32,
+	out_name: *mut *const c_char,
+) -> i32 {
+	guarded(root, |clients| match clients.iter().find(|c| c.id == client_id) {
+		Some(c) => {
+			unsafe { *out_name = c.name.as_ptr() };
+			0
+		}
+		None => -4,
+	})
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mnd_root_get_client_state(
+	root: *mut c_void,
+	client_id: u32,
+	out_flags: *mut u32,
+) -> i32 {
+	guarded(root, |clients| match clients.iter().find(|c| c.id == client_id) {
+		Some(c) => {
+			unsafe { *out_flags = c.state };
+			0
+		}
+		None => -4,
+	})
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mnd_root_set_client_primary(root: *mut c_void, client_id: u32) -> i32 {
+	guarded(root, |clients| {
+		for c in clients.iter_mut() {
+			c.state &= !1;
+		}
+		if let Some(c) = clients.iter_mut().find(|c| c.id == client_id) {
+			c.state |= 1;
+		}
+		0
+	})
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mnd_root_set_client_focused(root: *mut c_void, client_id: u32) -> i32 {
+	guarded(root, |clients| {
+		for c in clients.iter_mut() {
+			c.state &= !8;
+		}
+		if let Some(c) = clients.iter_mut().find(|c| c.id == client_id) {
+			c.state |= 8;
+		}
+		0
+	})
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mnd_root_toggle_client_io_active(root: *mut c_void, client_id: u32) -> i32 {
+	guarded(root, |clients| {
+		if let Some(c) = clients.iter_mut().find(|c| c.id == client_id) {
+			c.state ^= 32;
+		}
+		0
+	})
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mnd_root_get_device_count(root: *mut c_void, out_count: *mut u32) -> i32 {
+	guarded(root, |_clients| {
+		unsafe { *out_count = 0 };
+		0
+	})
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mnd_root_get_device_info(
+	root: *mut c_void,
+	_index: u32,
+	_out_id: *mut u32,
+	_out_name: *mut *const c_char,
+) -> i32 {
+	guarded(root, |_clients| -4)
+}
+"#;
+
+	fn build_stub_library() -> std::path::PathBuf {
+		let dir = std::env::temp_dir().join("libmonado-rs-test-stub");
+		std::fs::create_dir_all(&dir).unwrap();
+		let src_path = dir.join("stub.rs");
+		let lib_path = dir.join("libmonado_stub.so");
+		std::fs::write(&src_path, STUB_SOURCE).unwrap();
+		let status = std::process::Command::new("rustc")
+			.args(["--edition", "2021", "--crate-type", "cdylib", "-o"])
+			.arg(&lib_path)
+			.arg(&src_path)
+			.status()
+			.expect("rustc must be on PATH to build the fake libmonado.so test fixture");
+		assert!(
+			status.success(),
+			"failed to compile the fake libmonado.so test fixture"
+		);
+		lib_path
+	}
+
+	#[test]
+	fn shared_monado_serializes_concurrent_client_access() {
+		let lib_path = build_stub_library();
+		let monado = Monado::create(&lib_path).expect("failed to load the fake libmonado.so");
+		let shared = SharedMonado::new(monado);
+
+		let clients = shared.clients().unwrap();
+		assert_eq!(clients.len(), 2);
+
+		// Drive every client's setters and readers concurrently from two
+		// threads per client and confirm nothing panics or deadlocks,
+		// i.e. every `mnd_root_*` call for this root really was
+		// serialized by the mutex.
+		let handles: Vec<_> = clients
+			.iter()
+			.flat_map(|client| [client.clone(), client.clone()])
+			.map(|client| {
+				thread::spawn(move || {
+					for _ in 0..50 {
+						client.set_primary().unwrap();
+						client.set_focused().unwrap();
+						client.set_io_active(true).unwrap();
+						client.name().unwrap();
+						client.state().unwrap();
+					}
+				})
+			})
+			.collect();
+		for handle in handles {
+			handle.join().unwrap();
+		}
+
+		let names: Vec<String> = shared
+			.clients()
+			.unwrap()
+			.into_iter()
+			.map(|client| client.name().unwrap())
+			.collect();
+		assert_eq!(names.len(), 2);
+	}
+}